@@ -1,10 +1,13 @@
 use bitflags::bitflags;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use i2cdev::core::*;
 use i2cdev::linux::{LinuxI2CDevice, LinuxI2CError, LinuxI2CMessage};
+use serde::{Deserialize, Serialize};
+use spidev::{Spidev, SpidevTransfer};
 
 const DEFAULT_I2C_BUS: u8 = 1;
 const DEFAULT_CHIP_ADDR: u16 = 0x38;
+const DEFAULT_SPI_DEV: &str = "/dev/spidev0.0";
 const PORT_SIZE: u16 = 0x200;
 
 bitflags! {
@@ -35,7 +38,7 @@ bitflags! {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Port {
     Port0,
     Port1,
@@ -43,29 +46,214 @@ enum Port {
     Port3,
 }
 
-struct Pi7c9xg404 {
-    i2c_dev: LinuxI2CDevice,
+impl Port {
+    fn index(self) -> u8 {
+        match self {
+            Port::Port0 => 0,
+            Port::Port1 => 1,
+            Port::Port2 => 2,
+            Port::Port3 => 3,
+        }
+    }
 }
 
-impl Pi7c9xg404 {
-    pub fn init(i2c_dev: u8, i2c_addr: u16) -> Result<Pi7c9xg404, LinuxI2CError> {
-        let dev = unsafe { LinuxI2CDevice::force_new(format!("/dev/i2c-{}", i2c_dev), i2c_addr) }?;
-        Ok(Pi7c9xg404 { i2c_dev: dev })
+/// Errors that can occur while talking to a `Pi7c9xg404`.
+#[derive(Debug)]
+enum RegError {
+    /// The underlying I2C transaction failed for a reason other than a NACK.
+    Bus(LinuxI2CError),
+    /// The underlying SPI transaction failed.
+    SpiBus(std::io::Error),
+    /// The chip did not acknowledge the transaction, i.e. nothing is present at the configured address.
+    Nack,
+    /// `offset` does not fit in the transaction's 11-bit offset field.
+    OffsetOutOfRange(u16),
+    /// The `I2C_RDWR` ioctl completed fewer of the header/data message phases
+    /// than were submitted, e.g. arbitration was lost partway through a
+    /// transaction. This is a message-count check, not a byte-count one: a
+    /// short read of the data phase itself surfaces as a NACK or bus error
+    /// instead, since the ioctl only reports phases completed.
+    ShortTransfer,
+}
+
+impl std::fmt::Display for RegError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegError::Bus(e) => write!(f, "I2C bus error: {e}"),
+            RegError::SpiBus(e) => write!(f, "SPI bus error: {e}"),
+            RegError::Nack => write!(f, "device did not acknowledge (NACK) -- is it present?"),
+            RegError::OffsetOutOfRange(offset) => {
+                write!(f, "offset {offset:#06x} does not fit in the 11-bit offset field")
+            }
+            RegError::ShortTransfer => {
+                write!(f, "I2C_RDWR completed fewer message phases than submitted")
+            }
+        }
     }
+}
 
-    pub fn read_reg(&mut self, port: Port, offset: u16) -> Result<[u8; 4], LinuxI2CError> {
-        let mut write_data = [0; 0x4];
+impl std::error::Error for RegError {}
 
-        write_data[0] = B0Cmd::READ.bits();
+impl From<LinuxI2CError> for RegError {
+    fn from(e: LinuxI2CError) -> Self {
+        // i2c-dev surfaces a NACK as ENXIO ("No such device") or EREMOTEIO
+        // ("Remote I/O error"), depending on the bus adapter driver.
+        let msg = e.to_string();
+        if msg.contains("No such device") || msg.contains("Remote I/O error") {
+            RegError::Nack
+        } else {
+            RegError::Bus(e)
+        }
+    }
+}
 
-        write_data[1] = match port {
+/// Physical transport for the command/address header + data phase of a register transaction.
+///
+/// The header already encodes the read/write direction (`B0Cmd::READ`), the
+/// port select and offset bits, and the per-byte commit mask; only the
+/// framing of the header and data phases onto the wire differs between
+/// transports.
+trait RegAccess {
+    fn transfer_reg(&mut self, header: [u8; 4], data: &mut [u8]) -> Result<(), RegError>;
+}
+
+/// A TCA9548A-style I2C mux sitting upstream of the switch.
+///
+/// Selecting a channel is a single byte write to the mux's own address,
+/// where bit N enables downstream channel N. The last-selected channel is
+/// cached so repeated transfers to the same channel don't re-issue the
+/// selection write.
+struct Mux {
+    dev: LinuxI2CDevice,
+    current: Option<u8>,
+}
+
+impl Mux {
+    pub fn new(i2c_bus: u8, mux_addr: u16) -> Result<Mux, RegError> {
+        let dev = unsafe { LinuxI2CDevice::force_new(format!("/dev/i2c-{}", i2c_bus), mux_addr) }?;
+        Ok(Mux { dev, current: None })
+    }
+
+    pub fn select(&mut self, channel: u8) -> Result<(), RegError> {
+        if self.current == Some(channel) {
+            return Ok(());
+        }
+        self.dev.write(&[1 << channel])?;
+        self.current = Some(channel);
+        Ok(())
+    }
+}
+
+/// I2C transport, the chip's default interface.
+struct I2cBackend {
+    dev: LinuxI2CDevice,
+    mux: Option<(Mux, u8)>,
+}
+
+impl I2cBackend {
+    pub fn new(i2c_bus: u8, i2c_addr: u16, mux: Option<(Mux, u8)>) -> Result<I2cBackend, RegError> {
+        let dev = unsafe { LinuxI2CDevice::force_new(format!("/dev/i2c-{}", i2c_bus), i2c_addr) }?;
+        Ok(I2cBackend { dev, mux })
+    }
+}
+
+impl RegAccess for I2cBackend {
+    fn transfer_reg(&mut self, header: [u8; 4], data: &mut [u8]) -> Result<(), RegError> {
+        if let Some((mux, channel)) = &mut self.mux {
+            mux.select(*channel)?;
+        }
+
+        let is_read = header[0] & B0Cmd::READ.bits() != 0;
+        if is_read {
+            let mut msg = [LinuxI2CMessage::write(&header), LinuxI2CMessage::read(data)];
+            let done = self.dev.transfer(&mut msg)?;
+            if done as usize != msg.len() {
+                return Err(RegError::ShortTransfer);
+            }
+        } else {
+            // The data phase must follow the header without a repeated START,
+            // or the chip will reparse it as a new command header.
+            let mut payload = Vec::with_capacity(4 + data.len());
+            payload.extend_from_slice(&header);
+            payload.extend_from_slice(data);
+            let mut msg = [LinuxI2CMessage::write(&payload)];
+            let done = self.dev.transfer(&mut msg)?;
+            if done as usize != msg.len() {
+                return Err(RegError::ShortTransfer);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// SPI transport, available on boards that wire the chip's SPI pins instead of I2C.
+struct SpiBackend {
+    spi: Spidev,
+}
+
+impl SpiBackend {
+    pub fn new(path: &str) -> Result<SpiBackend, RegError> {
+        let spi = Spidev::open(path).map_err(RegError::SpiBus)?;
+        Ok(SpiBackend { spi })
+    }
+}
+
+impl RegAccess for SpiBackend {
+    fn transfer_reg(&mut self, header: [u8; 4], data: &mut [u8]) -> Result<(), RegError> {
+        let is_read = header[0] & B0Cmd::READ.bits() != 0;
+
+        // Header and data must ride the same CS-held transfer, or the chip
+        // loses the addressed register once CS deasserts after the header.
+        let mut tx = vec![0u8; 4 + data.len()];
+        tx[..4].copy_from_slice(&header);
+        if is_read {
+            let mut rx = vec![0u8; tx.len()];
+            self.spi
+                .transfer(&mut SpidevTransfer::read_write(&tx, &mut rx))
+                .map_err(RegError::SpiBus)?;
+            data.copy_from_slice(&rx[4..]);
+        } else {
+            tx[4..].copy_from_slice(data);
+            self.spi
+                .transfer(&mut SpidevTransfer::write(&tx))
+                .map_err(RegError::SpiBus)?;
+        }
+        Ok(())
+    }
+}
+
+impl RegAccess for Box<dyn RegAccess> {
+    fn transfer_reg(&mut self, header: [u8; 4], data: &mut [u8]) -> Result<(), RegError> {
+        (**self).transfer_reg(header, data)
+    }
+}
+
+struct Pi7c9xg404<T: RegAccess> {
+    backend: T,
+}
+
+impl<T: RegAccess> Pi7c9xg404<T> {
+    pub fn new(backend: T) -> Pi7c9xg404<T> {
+        Pi7c9xg404 { backend }
+    }
+
+    pub fn read_reg(&mut self, port: Port, offset: u16) -> Result<[u8; 4], RegError> {
+        if offset >= 1 << 11 {
+            return Err(RegError::OffsetOutOfRange(offset));
+        }
+
+        let mut header = [0; 0x4];
+
+        header[0] = B0Cmd::READ.bits();
+
+        header[1] = match port {
             Port::Port0 => B1PortSel::PORT0.bits(),
             Port::Port1 => B1PortSel::PORT1.bits(),
             Port::Port2 => B1PortSel::PORT2.bits(),
             Port::Port3 => B1PortSel::PORT3.bits(),
         };
 
-        write_data[2] = match port {
+        header[2] = match port {
             Port::Port0 => B2PortSel::PORT0.bits(),
             Port::Port1 => B2PortSel::PORT1.bits(),
             Port::Port2 => B2PortSel::PORT2.bits(),
@@ -73,32 +261,146 @@ impl Pi7c9xg404 {
         } | B2ByteEnable::ALL.bits()
             | (offset >> 10) as u8;
 
-        write_data[3] = (offset >> 2) as u8;
+        header[3] = (offset >> 2) as u8;
 
         let mut read_data = [0; 0x4];
-        let mut msg = [
-            LinuxI2CMessage::write(&write_data),
-            LinuxI2CMessage::read(&mut read_data),
-        ];
-        match self.i2c_dev.transfer(&mut msg) {
-            Ok(_) => (),
-            Err(e) => eprintln!("{e}"),
-        }
+        self.backend.transfer_reg(header, &mut read_data)?;
         Ok(read_data)
     }
 
-    pub fn print_port_regs(&mut self, port: Port) -> Result<(), LinuxI2CError> {
-        println!("Port: {:?}", port);
-        for reg in 0..(PORT_SIZE / 4) {
-            let val =self.read_reg(port, reg * 4)?;
-            print_reg(reg * 4, val);
+    pub fn write_reg(
+        &mut self,
+        port: Port,
+        offset: u16,
+        mut value: [u8; 4],
+        bytes: B2ByteEnable,
+    ) -> Result<(), RegError> {
+        if offset >= 1 << 11 {
+            return Err(RegError::OffsetOutOfRange(offset));
         }
+
+        let mut header = [0; 0x4];
+
+        header[1] = match port {
+            Port::Port0 => B1PortSel::PORT0.bits(),
+            Port::Port1 => B1PortSel::PORT1.bits(),
+            Port::Port2 => B1PortSel::PORT2.bits(),
+            Port::Port3 => B1PortSel::PORT3.bits(),
+        };
+
+        header[2] = match port {
+            Port::Port0 => B2PortSel::PORT0.bits(),
+            Port::Port1 => B2PortSel::PORT1.bits(),
+            Port::Port2 => B2PortSel::PORT2.bits(),
+            Port::Port3 => B2PortSel::PORT3.bits(),
+        } | bytes.bits()
+            | (offset >> 10) as u8;
+
+        header[3] = (offset >> 2) as u8;
+
+        self.backend.transfer_reg(header, &mut value)?;
         Ok(())
     }
+
+    /// Read every register of `port` in `[start, start + length)`, stepping by 4.
+    pub fn dump(&mut self, port: Port, start: u16, length: u16) -> Result<Vec<(u16, [u8; 4])>, RegError> {
+        let end = start.saturating_add(length);
+        let mut out = Vec::with_capacity((length / 4) as usize);
+        let mut offset = start;
+        while offset < end {
+            out.push((offset, self.read_reg(port, offset)?));
+            offset += 4;
+        }
+        Ok(out)
+    }
+}
+
+/// A named register, decoded from a `--decode` TOML map.
+#[derive(Deserialize)]
+struct RegisterDef {
+    offset: u16,
+    name: String,
+    #[serde(default, rename = "bitfield")]
+    bitfields: Vec<BitfieldDef>,
+}
+
+/// A named sub-range of a register's 32 bits, optionally an enum of known values.
+#[derive(Deserialize)]
+struct BitfieldDef {
+    name: String,
+    high_bit: u8,
+    low_bit: u8,
+    #[serde(default, rename = "enum")]
+    enum_values: std::collections::BTreeMap<u32, String>,
 }
 
-fn print_reg(reg_num: u16, val: [u8; 4]) {
-    println!("{:#06x}: {:#04x}  {:#04x} {:#04x} {:#04x}", reg_num, val[0], val[1], val[2], val[3]);
+/// A loaded `--decode` register map: offset -> symbolic name/bitfields.
+#[derive(Deserialize, Default)]
+struct RegisterMap {
+    #[serde(default, rename = "register")]
+    registers: Vec<RegisterDef>,
+}
+
+impl RegisterMap {
+    pub fn load(path: &str) -> Result<RegisterMap, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let map: RegisterMap = toml::from_str(&text).map_err(|e| format!("{path}: {e}"))?;
+        for reg in &map.registers {
+            for bf in &reg.bitfields {
+                if bf.low_bit > bf.high_bit || bf.high_bit > 31 {
+                    return Err(format!(
+                        "{path}: register {:#06x} bitfield {:?}: low_bit {} / high_bit {} must satisfy low_bit <= high_bit <= 31",
+                        reg.offset, bf.name, bf.low_bit, bf.high_bit
+                    ));
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    fn find(&self, offset: u16) -> Option<&RegisterDef> {
+        self.registers.iter().find(|r| r.offset == offset)
+    }
+}
+
+fn print_reg(reg_num: u16, val: [u8; 4], map: Option<&RegisterMap>) {
+    let Some(def) = map.and_then(|m| m.find(reg_num)) else {
+        println!("{:#06x}: {:#04x}  {:#04x} {:#04x} {:#04x}", reg_num, val[0], val[1], val[2], val[3]);
+        return;
+    };
+
+    let word = u32::from_le_bytes(val);
+    println!("{reg_num:#06x}: {:<24} {word:#010x}", def.name);
+    for bf in &def.bitfields {
+        let width = bf.high_bit - bf.low_bit + 1;
+        let mask = if width >= 32 { u32::MAX } else { (1u32 << width) - 1 };
+        let value = (word >> bf.low_bit) & mask;
+        match bf.enum_values.get(&value) {
+            Some(meaning) => println!("    {:<24} = {value:#x} ({meaning})", bf.name),
+            None => println!("    {:<24} = {value:#x}", bf.name),
+        }
+    }
+}
+
+fn print_csv_row(port: Port, offset: u16, val: [u8; 4]) {
+    println!(
+        "{},{offset:#06x},{:02x}{:02x}{:02x}{:02x},{:#010x}",
+        port.index(),
+        val[0],
+        val[1],
+        val[2],
+        val[3],
+        u32::from_le_bytes(val)
+    );
+}
+
+/// One decoded register, as emitted by `--format json`.
+#[derive(Serialize)]
+struct DumpEntry {
+    port: u8,
+    offset: u16,
+    bytes: [u8; 4],
+    u32_le: u32,
 }
 
 /// Convert a string slice to an integer, the base is determine from the prefix.
@@ -129,33 +431,241 @@ where
     }
 }
 
+fn parse_port(src: &str) -> Result<Port, String> {
+    match parse_prefixed_int::<u8>(src)? {
+        0 => Ok(Port::Port0),
+        1 => Ok(Port::Port1),
+        2 => Ok(Port::Port2),
+        3 => Ok(Port::Port3),
+        n => Err(format!("port {n} out of range, expected 0..3")),
+    }
+}
+
+/// Parse a `1234`-style byte selector (the digit is the 1-based position of
+/// the data byte as sent) into the matching `B2ByteEnable` bits.
+fn parse_byte_mask(src: &str) -> Result<B2ByteEnable, String> {
+    let mut bytes = B2ByteEnable::empty();
+    for c in src.chars() {
+        bytes |= match c {
+            '1' => B2ByteEnable::BYTE1,
+            '2' => B2ByteEnable::BYTE2,
+            '3' => B2ByteEnable::BYTE3,
+            '4' => B2ByteEnable::BYTE4,
+            _ => return Err(format!("invalid byte selector {c:?} in {src:?}, expected digits 1-4")),
+        };
+    }
+    if bytes.is_empty() {
+        return Err(format!("byte selector {src:?} must select at least one byte"));
+    }
+    Ok(bytes)
+}
+
+/// Parse a `PORT:OFFSET=VALUE` or `PORT:OFFSET=VALUE/BYTES` argument as produced by `--write`.
+///
+/// `BYTES` is a subset of `1234` selecting which data bytes are actually
+/// committed (e.g. `/13` commits only the first and third); it defaults to
+/// all four bytes when omitted.
+fn parse_write_arg(src: &str) -> Result<(Port, u16, [u8; 4], B2ByteEnable), String> {
+    let (assignment, mask) = match src.split_once('/') {
+        Some((assignment, mask)) => (assignment, parse_byte_mask(mask)?),
+        None => (src, B2ByteEnable::ALL),
+    };
+
+    let (port, rest) = assignment.split_once(':').ok_or_else(|| format!("expected PORT:OFFSET=VALUE, got {src:?}"))?;
+    let (offset, value) = rest.split_once('=').ok_or_else(|| format!("expected PORT:OFFSET=VALUE, got {src:?}"))?;
+
+    let port = parse_port(port)?;
+    let offset: u16 = parse_prefixed_int(offset)?;
+    let value: u32 = parse_prefixed_int(value)?;
+
+    Ok((port, offset, value.to_le_bytes(), mask))
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Interface {
+    I2c,
+    Spi,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    #[clap(long, value_enum, default_value_t = Interface::I2c)]
+    interface: Interface,
     #[clap(short, long, default_value_t = DEFAULT_I2C_BUS, value_parser = parse_prefixed_int::<u8>)]
     i2c_bus: u8,
     #[clap(short, long, default_value_t = DEFAULT_CHIP_ADDR, value_parser = parse_prefixed_int::<u16>)]
     chip_addr: u16,
+    /// Path to the SPI device node, only used with `--interface spi`.
+    #[clap(long, default_value = DEFAULT_SPI_DEV)]
+    spi_dev: String,
+    /// Address of an upstream TCA9548A-style I2C mux, only used with `--interface i2c`.
+    #[clap(long, value_parser = parse_prefixed_int::<u16>)]
+    mux_addr: Option<u16>,
+    /// Downstream mux channel (0..7) the switch is wired to; requires `--mux-addr`.
+    #[clap(long, value_parser = parse_prefixed_int::<u8>)]
+    mux_channel: Option<u8>,
+    /// Write VALUE to OFFSET on PORT instead of dumping registers, e.g.
+    /// `0:0x100=0xdeadbeef`, or `0:0x100=0xdeadbeef/13` to commit only bytes 1 and 3.
+    #[clap(short, long, value_parser = parse_write_arg)]
+    write: Option<(Port, u16, [u8; 4], B2ByteEnable)>,
+    /// Annotate the dump with register names and bitfields from a TOML register map.
+    #[clap(long)]
+    decode: Option<String>,
+    /// Port(s) to dump, repeatable; defaults to all four.
+    #[clap(long, value_parser = parse_port)]
+    port: Vec<Port>,
+    /// First offset to dump, within a port's register space.
+    #[clap(long, default_value_t = 0, value_parser = parse_prefixed_int::<u16>)]
+    offset: u16,
+    /// Number of bytes to dump, starting at `--offset`.
+    #[clap(long, default_value_t = PORT_SIZE, value_parser = parse_prefixed_int::<u16>)]
+    length: u16,
+    /// Output format for the dump.
+    #[clap(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    println!("/dev/i2c-{}: {:#04x}", cli.i2c_bus, cli.chip_addr);
+    if cli.interface == Interface::Spi && (cli.mux_addr.is_some() || cli.mux_channel.is_some()) {
+        eprintln!("ERROR: --mux-addr/--mux-channel select an I2C mux and can't be used with --interface spi");
+        std::process::exit(-1);
+    }
+
+    let mux = match (cli.mux_addr, cli.mux_channel) {
+        (Some(addr), Some(channel)) => {
+            if channel >= 8 {
+                eprintln!("ERROR: mux channel {channel} out of range, expected 0..7");
+                std::process::exit(-1);
+            }
+            match Mux::new(cli.i2c_bus, addr) {
+                Ok(mux) => Some((mux, channel)),
+                Err(e) => {
+                    eprintln!("ERROR: Can't access mux {addr:#04x}@i2c-{}: {e}", cli.i2c_bus);
+                    std::process::exit(-1);
+                }
+            }
+        }
+        (None, None) => None,
+        _ => {
+            eprintln!("ERROR: --mux-addr and --mux-channel must be given together");
+            std::process::exit(-1);
+        }
+    };
+
+    let backend: Result<Box<dyn RegAccess>, RegError> = match cli.interface {
+        Interface::I2c => {
+            eprintln!("/dev/i2c-{}: {:#04x}", cli.i2c_bus, cli.chip_addr);
+            I2cBackend::new(cli.i2c_bus, cli.chip_addr, mux).map(|b| Box::new(b) as Box<dyn RegAccess>)
+        }
+        Interface::Spi => {
+            eprintln!("{}", cli.spi_dev);
+            SpiBackend::new(&cli.spi_dev).map(|b| Box::new(b) as Box<dyn RegAccess>)
+        }
+    };
+    let mut pi7c9xg404 = match backend {
+        Ok(backend) => Pi7c9xg404::new(backend),
+        Err(e) => {
+            eprintln!("ERROR: Can't access device: {e}");
+            std::process::exit(-1);
+        }
+    };
+
+    if let Some((port, offset, value, bytes)) = cli.write {
+        if let Err(e) = pi7c9xg404.write_reg(port, offset, value, bytes) {
+            eprintln!("ERROR: write to {port:?}:{offset:#06x} failed: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    let mut pi7c9xg404 = match Pi7c9xg404::init(cli.i2c_bus, cli.chip_addr) {
-        Ok(pi7c9xg404) => pi7c9xg404,
+    let map = cli.decode.map(|path| match RegisterMap::load(&path) {
+        Ok(map) => map,
         Err(e) => {
-            eprintln!(
-                "ERROR: Can't access device {:#04x}@i2c-{}: {e}",
-                cli.chip_addr, cli.i2c_bus
-            );
+            eprintln!("ERROR: Can't load register map: {e}");
             std::process::exit(-1);
         }
+    });
+
+    let ports = if cli.port.is_empty() {
+        vec![Port::Port0, Port::Port1, Port::Port2, Port::Port3]
+    } else {
+        cli.port
     };
 
-    pi7c9xg404.print_port_regs(Port::Port0).unwrap();
-    pi7c9xg404.print_port_regs(Port::Port1).unwrap();
-    pi7c9xg404.print_port_regs(Port::Port2).unwrap();
-    pi7c9xg404.print_port_regs(Port::Port3).unwrap();
+    if cli.format == Format::Csv {
+        println!("port,offset,bytes,u32_le");
+    }
+
+    let mut json_entries = Vec::new();
+    let mut had_error = false;
+    for port in ports {
+        let entries = match pi7c9xg404.dump(port, cli.offset, cli.length) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("ERROR: dumping {port:?} failed: {e}");
+                had_error = true;
+                continue;
+            }
+        };
+        match cli.format {
+            Format::Text => {
+                println!("Port: {:?}", port);
+                for (offset, val) in entries {
+                    print_reg(offset, val, map.as_ref());
+                }
+            }
+            Format::Csv => {
+                for (offset, val) in entries {
+                    print_csv_row(port, offset, val);
+                }
+            }
+            Format::Json => {
+                json_entries.extend(entries.into_iter().map(|(offset, bytes)| DumpEntry {
+                    port: port.index(),
+                    offset,
+                    bytes,
+                    u32_le: u32::from_le_bytes(bytes),
+                }));
+            }
+        }
+    }
+    if cli.format == Format::Json {
+        println!("{}", serde_json::to_string_pretty(&json_entries).unwrap());
+    }
+    if had_error {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_write_arg_defaults_to_all_bytes_little_endian() {
+        let (port, offset, value, bytes) = parse_write_arg("0:0x100=0xdeadbeef").unwrap();
+        assert_eq!(port, Port::Port0);
+        assert_eq!(offset, 0x100);
+        assert_eq!(value, 0xdeadbeefu32.to_le_bytes());
+        assert_eq!(bytes, B2ByteEnable::ALL);
+    }
+
+    #[test]
+    fn parse_write_arg_honors_byte_mask_suffix() {
+        let (port, offset, value, bytes) = parse_write_arg("2:0x200=0xdeadbeef/13").unwrap();
+        assert_eq!(port, Port::Port2);
+        assert_eq!(offset, 0x200);
+        assert_eq!(value, 0xdeadbeefu32.to_le_bytes());
+        assert_eq!(bytes, B2ByteEnable::BYTE1 | B2ByteEnable::BYTE3);
+    }
 }